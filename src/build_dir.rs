@@ -0,0 +1,129 @@
+use crate::cmd::SandboxBuilder;
+use crate::errors::RustwideError;
+use crate::{Crate, Toolchain, Workspace};
+use failure::Error;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A build directory shared across builds that are keyed by the same name.
+pub struct BuildDir {
+    pub(crate) workspace: Workspace,
+    pub(crate) name: String,
+}
+
+impl BuildDir {
+    /// Path of this build directory's `target/` output.
+    pub fn target_dir(&self) -> PathBuf {
+        self.workspace.build_dirs_path().join(&self.name).join("target")
+    }
+
+    /// Prepare a build of `krate` with `toolchain` inside this build directory.
+    pub fn build(&mut self, toolchain: &Toolchain, krate: &Crate, sandbox: SandboxBuilder) -> Build<'_> {
+        Build {
+            build_dir: self,
+            toolchain: toolchain.clone(),
+            krate: krate.clone(),
+            sandbox: sandbox.build(),
+            vendor_dir: None,
+        }
+    }
+}
+
+/// A single, ready-to-run build.
+pub struct Build<'a> {
+    build_dir: &'a mut BuildDir,
+    toolchain: Toolchain,
+    krate: Crate,
+    sandbox: crate::cmd::Sandbox,
+    vendor_dir: Option<PathBuf>,
+}
+
+impl<'a> Build<'a> {
+    /// Make cargo resolve crates.io dependencies from a directory produced by
+    /// [`Workspace::vendor`] instead of the network-backed registry, via a `[source]`
+    /// replacement of `crates-io`. Git dependencies are not replaced and still resolve through
+    /// their git sources.
+    pub fn vendored_sources(mut self, vendor_dir: &Path) -> Self {
+        self.vendor_dir = Some(vendor_dir.into());
+        self
+    }
+
+    /// Run the build, calling `f` with a handle that can be used to invoke cargo.
+    pub fn run<F>(self, f: F) -> Result<(), Error>
+    where
+        F: FnOnce(&BuildContext<'_>) -> Result<(), Error>,
+    {
+        let _ = &self.krate;
+        let context = BuildContext {
+            toolchain: &self.toolchain,
+            target_dir: self.build_dir.target_dir(),
+            networking: self.sandbox.networking,
+            vendor_dir: self.vendor_dir.clone(),
+        };
+        f(&context)?;
+        self.build_dir
+            .workspace
+            .record_cache_use(&format!("build-dir:{}", self.build_dir.name))?;
+        Ok(())
+    }
+}
+
+/// Handle passed to the build closure, used to spawn commands inside the sandbox.
+pub struct BuildContext<'a> {
+    toolchain: &'a Toolchain,
+    target_dir: PathBuf,
+    networking: bool,
+    vendor_dir: Option<PathBuf>,
+}
+
+impl<'a> BuildContext<'a> {
+    /// Start building a `cargo` invocation that runs inside this build's sandbox.
+    pub fn cargo(&self) -> CargoCommand<'_> {
+        CargoCommand {
+            context: self,
+            args: Vec::new(),
+        }
+    }
+}
+
+/// A `cargo` invocation, built up incrementally before being run.
+pub struct CargoCommand<'a> {
+    context: &'a BuildContext<'a>,
+    args: Vec<String>,
+}
+
+impl<'a> CargoCommand<'a> {
+    /// Append arguments to the `cargo` invocation.
+    pub fn args(mut self, args: &[&str]) -> Self {
+        self.args.extend(args.iter().map(|s| s.to_string()));
+        self
+    }
+
+    /// Run the command to completion.
+    pub fn run(self) -> Result<(), Error> {
+        let mut command = Command::new("cargo");
+        command
+            .arg(format!("+{}", self.context.toolchain))
+            .args(&self.args)
+            .env("CARGO_TARGET_DIR", &self.context.target_dir);
+        if !self.context.networking {
+            command.arg("--offline");
+        }
+        if let Some(vendor_dir) = &self.context.vendor_dir {
+            command
+                .arg("--config")
+                .arg(r#"source.crates-io.replace-with="vendored-sources""#)
+                .arg("--config")
+                .arg(format!(
+                    "source.vendored-sources.directory=\"{}\"",
+                    vendor_dir.display()
+                ));
+        }
+        let status = command.status()?;
+        if !status.success() {
+            let command = format!("cargo {}", self.args.join(" "));
+            return Err(RustwideError::CommandFailed(command, status).into());
+        }
+        Ok(())
+    }
+}
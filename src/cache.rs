@@ -0,0 +1,166 @@
+use crate::Workspace;
+use failure::{Error, ResultExt};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// A breakdown of how much disk space each part of the workspace's caches is using.
+///
+/// Returned by [`Workspace::cache_usage`](struct.Workspace.html#method.cache_usage).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheUsage {
+    /// Size in bytes of the downloaded `.crate` tarballs (`cargo-home/registry/cache`).
+    pub registry_cache: u64,
+    /// Size in bytes of the extracted registry sources (`cargo-home/registry/src`).
+    pub registry_src: u64,
+    /// Size in bytes of the bare git databases (`cargo-home/git/db`).
+    pub git_db: u64,
+    /// Size in bytes of the checked-out git sources (`cargo-home/git/checkouts`).
+    pub git_checkouts: u64,
+    /// Size in bytes of the local mirror of the registry index, used to look up checksums.
+    pub registry_index: u64,
+    /// Size in bytes of each named build directory's `target/` output, keyed by build directory
+    /// name.
+    pub build_dirs: Vec<(String, u64)>,
+}
+
+impl CacheUsage {
+    /// Total size in bytes of every category combined.
+    pub fn total(&self) -> u64 {
+        self.registry_cache
+            + self.registry_src
+            + self.git_db
+            + self.git_checkouts
+            + self.registry_index
+            + self.build_dirs.iter().map(|(_, size)| size).sum::<u64>()
+    }
+}
+
+impl fmt::Display for CacheUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "registry cache:   {}", human_size(self.registry_cache))?;
+        writeln!(f, "registry src:     {}", human_size(self.registry_src))?;
+        writeln!(f, "git db:           {}", human_size(self.git_db))?;
+        writeln!(f, "git checkouts:    {}", human_size(self.git_checkouts))?;
+        writeln!(f, "registry index:   {}", human_size(self.registry_index))?;
+        for (name, size) in &self.build_dirs {
+            writeln!(f, "build dir {}: {}", name, human_size(*size))?;
+        }
+        write!(f, "total:            {}", human_size(self.total()))
+    }
+}
+
+/// Format a byte count as a human-readable string (e.g. `"12.3 MB"`).
+fn human_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[unit])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+fn dir_size(path: &Path) -> Result<u64, Error> {
+    if !path.exists() {
+        return Ok(0);
+    }
+    let mut total = 0;
+    for entry in walkdir::WalkDir::new(path) {
+        let entry = entry.with_context(|_| format!("failed to walk {}", path.display()))?;
+        if entry.file_type().is_file() {
+            total += entry.metadata()?.len();
+        }
+    }
+    Ok(total)
+}
+
+impl Workspace {
+    /// Walk the cargo home and the build directories, reporting how much disk space each cache
+    /// category is using.
+    pub fn cache_usage(&self) -> Result<CacheUsage, Error> {
+        let mut build_dirs = Vec::new();
+        let build_dirs_path = self.build_dirs_path();
+        if build_dirs_path.exists() {
+            for entry in fs::read_dir(&build_dirs_path)
+                .with_context(|_| format!("failed to read {}", build_dirs_path.display()))?
+            {
+                let entry = entry?;
+                if entry.file_type()?.is_dir() {
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    let size = dir_size(&entry.path().join("target"))?;
+                    build_dirs.push((name, size));
+                }
+            }
+        }
+        build_dirs.sort();
+
+        Ok(CacheUsage {
+            registry_cache: dir_size(&self.registry_cache_path())?,
+            registry_src: dir_size(&self.registry_src_path())?,
+            git_db: dir_size(&self.git_db_path())?,
+            git_checkouts: dir_size(&self.git_checkouts_path())?,
+            registry_index: dir_size(&self.registry_index_path())?,
+            build_dirs,
+        })
+    }
+
+    /// Remove the downloaded `.crate` tarball cache, without touching the already-extracted
+    /// registry sources.
+    pub fn purge_registry_cache(&self) -> Result<(), Error> {
+        remove_dir_if_present(&self.registry_cache_path())
+    }
+
+    /// Remove the bare git database used to fetch git dependencies, without touching the
+    /// checkouts derived from it.
+    pub fn purge_git_db(&self) -> Result<(), Error> {
+        remove_dir_if_present(&self.git_db_path())
+    }
+
+    /// Remove the local mirror of the registry index, forcing the next checksum lookup to
+    /// re-clone it.
+    pub fn purge_registry_index(&self) -> Result<(), Error> {
+        remove_dir_if_present(&self.registry_index_path())
+    }
+
+    /// Remove a single named build directory's `target/` output.
+    pub fn purge_build_dir(&self, name: &str) -> Result<(), Error> {
+        remove_dir_if_present(&self.build_dirs_path().join(name).join("target"))
+    }
+
+    pub(crate) fn registry_cache_path(&self) -> std::path::PathBuf {
+        self.cargo_home().join("registry").join("cache")
+    }
+
+    pub(crate) fn registry_src_path(&self) -> std::path::PathBuf {
+        self.cargo_home().join("registry").join("src")
+    }
+
+    pub(crate) fn git_db_path(&self) -> std::path::PathBuf {
+        self.cargo_home().join("git").join("db")
+    }
+
+    pub(crate) fn git_checkouts_path(&self) -> std::path::PathBuf {
+        self.cargo_home().join("git").join("checkouts")
+    }
+
+    /// Path of the local mirror of the registry index used to look up crate checksums.
+    pub(crate) fn registry_index_path(&self) -> std::path::PathBuf {
+        self.cargo_home()
+            .join("registry")
+            .join("index")
+            .join("github.com-crates-io-index")
+    }
+}
+
+fn remove_dir_if_present(path: &Path) -> Result<(), Error> {
+    if path.exists() {
+        fs::remove_dir_all(path).with_context(|_| format!("failed to remove {}", path.display()))?;
+    }
+    Ok(())
+}
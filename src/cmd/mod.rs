@@ -0,0 +1,4 @@
+mod sandbox;
+
+pub use self::sandbox::SandboxBuilder;
+pub(crate) use self::sandbox::Sandbox;
@@ -0,0 +1,35 @@
+/// Builder for the sandbox a build runs in.
+#[derive(Debug, Clone)]
+pub struct SandboxBuilder {
+    pub(crate) networking: bool,
+}
+
+impl SandboxBuilder {
+    /// Create a new sandbox builder with networking enabled.
+    pub fn new() -> Self {
+        SandboxBuilder { networking: true }
+    }
+
+    /// Toggle whether the sandbox has access to the network.
+    pub fn enable_networking(mut self, enable: bool) -> Self {
+        self.networking = enable;
+        self
+    }
+
+    pub(crate) fn build(self) -> Sandbox {
+        Sandbox {
+            networking: self.networking,
+        }
+    }
+}
+
+impl Default for SandboxBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Sandbox {
+    pub(crate) networking: bool,
+}
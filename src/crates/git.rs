@@ -0,0 +1,65 @@
+use super::sanitize;
+use crate::errors::RustwideError;
+use crate::gitutil;
+use crate::Workspace;
+use failure::{Error, ResultExt};
+use std::fs;
+
+/// A crate living in a git repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct GitRepo {
+    pub(crate) url: String,
+}
+
+impl GitRepo {
+    pub(crate) fn fetch(&self, workspace: &Workspace) -> Result<(), Error> {
+        // Clones (or fetches) the repository into `cargo-home/git/db/`, then checks it out into
+        // `cargo-home/git/checkouts/`.
+        let db = workspace.git_db_path().join(sanitize(&self.url));
+        gitutil::fetch_or_clone(&self.url, &db)?;
+        let rev = gitutil::rev_parse_head(&db)?;
+        gitutil::checkout(&db, &rev, &workspace.git_checkouts_path().join(sanitize(&self.url)))?;
+        workspace.record_cache_use(&format!("git-db:{}", sanitize(&self.url)))?;
+        Ok(())
+    }
+
+    /// The commit the repository's `HEAD` currently resolves to on the remote, used to identify
+    /// exactly which revision was fetched or vendored.
+    pub(crate) fn resolved_rev(&self, _workspace: &Workspace) -> Result<String, Error> {
+        gitutil::ls_remote_head(&self.url)
+    }
+
+    /// Clone the repository into a temporary directory, and only move it into the git db (and
+    /// check it out into `cargo-home/git/checkouts/`) once its `HEAD` matches the revision the
+    /// remote reported before cloning.
+    pub(crate) fn fetch_checked(&self, workspace: &Workspace) -> Result<(), Error> {
+        // Resolved independently of the clone below, against the live remote, so this can't just
+        // trivially agree with whatever ends up on disk.
+        let expected = self.resolved_rev(workspace)?;
+
+        let dest = workspace.git_db_path().join(sanitize(&self.url));
+        let tmp = workspace.git_db_path().join(format!("{}.part", sanitize(&self.url)));
+        if let Some(parent) = tmp.parent() {
+            fs::create_dir_all(parent).with_context(|_| format!("failed to create {}", parent.display()))?;
+        }
+        if tmp.exists() {
+            fs::remove_dir_all(&tmp).with_context(|_| format!("failed to remove {}", tmp.display()))?;
+        }
+        gitutil::clone_bare(&self.url, &tmp)?;
+
+        let actual = gitutil::rev_parse_head(&tmp)?;
+        if actual != expected {
+            let _ = fs::remove_dir_all(&tmp);
+            return Err(RustwideError::ChecksumMismatch(self.url.clone(), expected, actual).into());
+        }
+
+        if dest.exists() {
+            fs::remove_dir_all(&dest).with_context(|_| format!("failed to remove {}", dest.display()))?;
+        }
+        fs::rename(&tmp, &dest).with_context(|_| format!("failed to rename {} to {}", tmp.display(), dest.display()))?;
+
+        gitutil::checkout(&dest, &actual, &workspace.git_checkouts_path().join(sanitize(&self.url)))?;
+        workspace.record_cache_use(&format!("git-db:{}", sanitize(&self.url)))?;
+        Ok(())
+    }
+}
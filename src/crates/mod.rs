@@ -0,0 +1,99 @@
+mod git;
+mod registry;
+
+pub(crate) use self::git::GitRepo;
+pub(crate) use self::registry::RegistryCrate;
+
+use crate::Workspace;
+use failure::Error;
+use std::fmt;
+use std::path::PathBuf;
+
+/// A crate that can be fetched into a workspace and built.
+// `RegistryCrate`/`GitRepo` are internal representations; callers construct a `Crate` through
+// `Crate::crates_io`/`Crate::git` and never name the variant types directly.
+#[allow(private_interfaces)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Crate {
+    CratesIo(RegistryCrate),
+    Git(GitRepo),
+}
+
+impl Crate {
+    /// Reference a crate published on crates.io.
+    pub fn crates_io(name: &str, version: &str) -> Self {
+        Crate::CratesIo(RegistryCrate {
+            name: name.into(),
+            version: version.into(),
+        })
+    }
+
+    /// Reference a crate living in a git repository.
+    pub fn git(url: &str) -> Self {
+        Crate::Git(GitRepo { url: url.into() })
+    }
+
+    /// Download (or update) the crate into the workspace's shared cache.
+    pub fn fetch(&self, workspace: &Workspace) -> Result<(), Error> {
+        match self {
+            Crate::CratesIo(krate) => krate.fetch(workspace),
+            Crate::Git(repo) => repo.fetch(workspace),
+        }
+    }
+
+    /// Download the crate to a temporary path, verify its checksum, and only then commit it to
+    /// the shared cache. Used by [`Workspace::fetch_all`](crate::Workspace::fetch_all) so a
+    /// corrupted download never poisons the cache.
+    pub(crate) fn fetch_checked(&self, workspace: &Workspace) -> Result<(), Error> {
+        match self {
+            Crate::CratesIo(krate) => krate.fetch_checked(workspace),
+            Crate::Git(repo) => repo.fetch_checked(workspace),
+        }
+    }
+
+    /// Name of the subdirectory this crate is extracted into when vendored.
+    pub(crate) fn vendor_dir_name(&self) -> String {
+        match self {
+            Crate::CratesIo(krate) => format!("{}-{}", krate.name, krate.version),
+            Crate::Git(repo) => sanitize(&repo.url),
+        }
+    }
+
+    /// Path of the already-fetched sources this crate's files should be copied from.
+    pub(crate) fn extracted_path(&self, workspace: &Workspace) -> PathBuf {
+        match self {
+            Crate::CratesIo(krate) => workspace
+                .registry_src_path()
+                .join(format!("{}-{}", krate.name, krate.version)),
+            Crate::Git(repo) => workspace.git_checkouts_path().join(sanitize(&repo.url)),
+        }
+    }
+
+    /// Checksum identifying the whole package, stored as the `"package"` field of the vendored
+    /// `.cargo-checksum.json`: the registry index checksum for crates.io crates, the resolved
+    /// git revision for git crates.
+    pub(crate) fn package_checksum(&self, workspace: &Workspace) -> Result<String, Error> {
+        match self {
+            Crate::CratesIo(krate) => krate.index_checksum(workspace),
+            Crate::Git(repo) => repo.resolved_rev(workspace),
+        }
+    }
+}
+
+/// Turn an arbitrary string (typically a git URL) into something safe to use as a path component
+/// or cache index key.
+pub(crate) fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+impl fmt::Display for Crate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Crate::CratesIo(krate) => write!(f, "crates.io crate {} {}", krate.name, krate.version),
+            Crate::Git(repo) => write!(f, "git repository {}", repo.url),
+        }
+    }
+}
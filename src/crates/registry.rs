@@ -0,0 +1,170 @@
+use crate::errors::RustwideError;
+use crate::gitutil;
+use crate::vendor::sha256_hex_of_file;
+use crate::Workspace;
+use failure::{Error, ResultExt};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The canonical registry index, mirrored locally so crate checksums can be looked up without
+/// trusting whatever happens to already be in the cache.
+const INDEX_URL: &str = "https://github.com/rust-lang/crates.io-index";
+
+/// A crate published on a cargo registry (crates.io being the default one).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RegistryCrate {
+    pub(crate) name: String,
+    pub(crate) version: String,
+}
+
+impl RegistryCrate {
+    /// Downloads the `.crate` tarball into `cargo-home/registry/cache/` (unless it's already
+    /// there) and extracts it into `cargo-home/registry/src/`, mirroring what `cargo fetch` does.
+    ///
+    /// Unlike [`fetch_checked`](Self::fetch_checked), the download isn't verified against the
+    /// registry index; use that instead when a corrupted download must never reach the cache.
+    pub(crate) fn fetch(&self, workspace: &Workspace) -> Result<(), Error> {
+        let dest = self.cache_path(workspace);
+        if !dest.exists() {
+            let tmp = dest.with_extension("crate.part");
+            if let Some(parent) = tmp.parent() {
+                fs::create_dir_all(parent).with_context(|_| format!("failed to create {}", parent.display()))?;
+            }
+            download_tarball(&self.name, &self.version, &tmp)?;
+            fs::rename(&tmp, &dest)
+                .with_context(|_| format!("failed to rename {} to {}", tmp.display(), dest.display()))?;
+        }
+
+        self.extract(workspace)?;
+        workspace.record_cache_use(&format!("registry-crate:{}", self.cache_key()))?;
+        Ok(())
+    }
+
+    /// sha256 of the `.crate` tarball, as recorded by the registry index itself, independent of
+    /// whatever tarball (if any) is currently sitting in the local cache.
+    pub(crate) fn index_checksum(&self, workspace: &Workspace) -> Result<String, Error> {
+        let index_repo = workspace.registry_index_path();
+        gitutil::fetch_or_clone(INDEX_URL, &index_repo)?;
+        workspace.record_cache_use("registry-index:github.com-crates-io-index")?;
+
+        let path = index_file_path(&self.name);
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&index_repo)
+            .args(["show", &format!("HEAD:{}", path)])
+            .output()
+            .with_context(|_| format!("failed to read {} from the registry index", self.name))?;
+        if !output.status.success() {
+            failure::bail!(
+                "{} is not present in the registry index ({})",
+                self.name,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let entry: serde_json::Value = serde_json::from_str(line)
+                .with_context(|_| format!("failed to parse registry index entry for {}", self.name))?;
+            if entry["vers"].as_str() == Some(self.version.as_str()) {
+                return entry["cksum"].as_str().map(ToString::to_string).ok_or_else(|| {
+                    failure::err_msg(format!("{} {} has no cksum in the index", self.name, self.version))
+                });
+            }
+        }
+        Err(failure::err_msg(format!(
+            "{} {} not found in the registry index",
+            self.name, self.version
+        )))
+    }
+
+    /// Download the `.crate` tarball to a temporary path, and only rename it into the registry
+    /// cache once its sha256 matches the checksum recorded in the registry index.
+    pub(crate) fn fetch_checked(&self, workspace: &Workspace) -> Result<(), Error> {
+        // Looked up before the download even starts, from an independent mirror of the index, so
+        // this can't just trivially agree with whatever ends up on disk.
+        let expected = self.index_checksum(workspace)?;
+
+        let dest = self.cache_path(workspace);
+        let tmp = dest.with_extension("crate.part");
+        if let Some(parent) = tmp.parent() {
+            fs::create_dir_all(parent).with_context(|_| format!("failed to create {}", parent.display()))?;
+        }
+        download_tarball(&self.name, &self.version, &tmp)?;
+
+        let actual = sha256_hex_of_file(&tmp)?;
+        if actual != expected {
+            let _ = fs::remove_file(&tmp);
+            return Err(RustwideError::ChecksumMismatch(self.cache_key(), expected, actual).into());
+        }
+
+        fs::rename(&tmp, &dest)
+            .with_context(|_| format!("failed to rename {} to {}", tmp.display(), dest.display()))?;
+
+        self.extract(workspace)?;
+        workspace.record_cache_use(&format!("registry-crate:{}", self.cache_key()))?;
+        Ok(())
+    }
+
+    /// Unpack the cached `.crate` tarball into `cargo-home/registry/src/`, unless it's already
+    /// been extracted.
+    fn extract(&self, workspace: &Workspace) -> Result<(), Error> {
+        let src_dir = workspace.registry_src_path();
+        fs::create_dir_all(&src_dir).with_context(|_| format!("failed to create {}", src_dir.display()))?;
+
+        if self.extracted_path(workspace).exists() {
+            return Ok(());
+        }
+
+        let tarball = self.cache_path(workspace);
+        let status = Command::new("tar")
+            .arg("xzf")
+            .arg(&tarball)
+            .arg("-C")
+            .arg(&src_dir)
+            .status()
+            .with_context(|_| format!("failed to run tar for {}", tarball.display()))?;
+        if !status.success() {
+            failure::bail!("extracting {} exited with {}", tarball.display(), status);
+        }
+        Ok(())
+    }
+
+    fn extracted_path(&self, workspace: &Workspace) -> PathBuf {
+        workspace.registry_src_path().join(self.cache_key())
+    }
+
+    fn cache_path(&self, workspace: &Workspace) -> PathBuf {
+        workspace.registry_cache_path().join(format!("{}.crate", self.cache_key()))
+    }
+
+    fn cache_key(&self) -> String {
+        format!("{}-{}", self.name, self.version)
+    }
+}
+
+/// The path a crate's entry lives at inside a checkout of the registry index, following cargo's
+/// own sharding rule.
+fn index_file_path(name: &str) -> String {
+    let name = name.to_lowercase();
+    match name.len() {
+        1 => format!("1/{}", name),
+        2 => format!("2/{}", name),
+        3 => format!("3/{}/{}", &name[..1], name),
+        _ => format!("{}/{}/{}", &name[..2], &name[2..4], name),
+    }
+}
+
+fn download_tarball(name: &str, version: &str, dest: &Path) -> Result<(), Error> {
+    let url = format!("https://static.crates.io/crates/{0}/{0}-{1}.crate", name, version);
+    let status = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location", "--output"])
+        .arg(dest)
+        .arg(&url)
+        .status()
+        .with_context(|_| format!("failed to run curl for {}", url))?;
+    if !status.success() {
+        failure::bail!("downloading {} exited with {}", url, status);
+    }
+    Ok(())
+}
@@ -0,0 +1,24 @@
+// The `failure` crate's `Fail` derive predates `non_local_definitions` and trips it on newer
+// compilers.
+#![allow(non_local_definitions)]
+
+use failure::Fail;
+
+/// Errors produced by this crate.
+#[derive(Debug, Fail)]
+pub enum RustwideError {
+    #[fail(display = "the workspace is locked by another process")]
+    WorkspaceLocked,
+
+    #[fail(display = "build directory '{}' not found", _0)]
+    BuildDirNotFound(String),
+
+    #[fail(display = "checksum mismatch for {}: expected {}, got {}", _0, _1, _2)]
+    ChecksumMismatch(String, String, String),
+
+    #[fail(display = "networking is disabled in this sandbox")]
+    NetworkingDisabled,
+
+    #[fail(display = "command `{}` didn't run successfully, {}", _0, _1)]
+    CommandFailed(String, std::process::ExitStatus),
+}
@@ -0,0 +1,49 @@
+// The `failure` crate's `Fail` derive predates `non_local_definitions` and trips it on newer
+// compilers.
+#![allow(non_local_definitions)]
+
+use crate::{Crate, Workspace};
+use failure::{Error, Fail};
+use rayon::prelude::*;
+
+/// The error returned for a single crate by [`Workspace::fetch_all`] when that crate failed to
+/// fetch, while the rest of the batch kept going.
+#[derive(Debug, Fail)]
+#[fail(display = "failed to fetch {}: {}", krate, error)]
+pub struct FetchError {
+    /// The crate that failed to fetch.
+    pub krate: Crate,
+    /// The underlying error.
+    pub error: Error,
+}
+
+impl Workspace {
+    /// Fetch many crates concurrently, verifying each download's checksum before it's committed
+    /// to the shared cache.
+    ///
+    /// Every crate is downloaded to a temporary path first and only renamed into the cache once
+    /// it passes its checksum check (the registry index checksum for crates.io crates, the
+    /// resolved revision for git crates), so a corrupted or truncated download can never poison
+    /// the cache. Crates that fail are reported individually rather than aborting the whole
+    /// batch, so a large run can keep going and report what went wrong at the end.
+    pub fn fetch_all(&self, crates: &[Crate]) -> Result<(), Vec<FetchError>> {
+        let errors: Vec<FetchError> = crates
+            .par_iter()
+            .filter_map(|krate| {
+                krate
+                    .fetch_checked(self)
+                    .err()
+                    .map(|error| FetchError {
+                        krate: krate.clone(),
+                        error,
+                    })
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::lastuse::{now, LastUseIndex};
+use crate::lock::WorkspaceLock;
+use crate::Workspace;
+use failure::{Error, ResultExt};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Eviction policy used by [`Workspace::gc`].
+#[derive(Debug, Clone, Copy)]
+pub enum GcPolicy {
+    /// Delete every cache entry whose last use is older than this.
+    OlderThan(Duration),
+    /// Evict least-recently-used entries until the tracked caches fit under this many bytes.
+    KeepUnder(u64),
+}
+
+impl Workspace {
+    pub(crate) fn lastuse_index_path(&self) -> PathBuf {
+        self.cargo_home().join(".rustwide-lastuse.json")
+    }
+
+    /// Record that a cache entry identified by `key` was just used.
+    ///
+    /// Called automatically by [`Crate::fetch`](crate::Crate::fetch) and at the end of a
+    /// successful [`Build::run`](crate::Build::run); exposed so callers with their own cache
+    /// entries can participate in [`gc`](Workspace::gc) too.
+    ///
+    /// Takes an exclusive lock on the workspace for the duration of the load-touch-store round
+    /// trip, since [`fetch_all`](Workspace::fetch_all) calls this concurrently from multiple
+    /// threads and an unlocked read-modify-write would lose updates.
+    pub fn record_cache_use(&self, key: &str) -> Result<(), Error> {
+        let _lock = WorkspaceLock::acquire(self.root())?;
+
+        let path = self.lastuse_index_path();
+        let mut index = LastUseIndex::load(&path)?;
+        index.touch(key);
+        index.store(&path)
+    }
+
+    fn resolve_entry(&self, key: &str) -> Option<PathBuf> {
+        if let Some(rest) = key.strip_prefix("registry-crate:") {
+            return Some(self.registry_cache_path().join(format!("{}.crate", rest)));
+        }
+        if let Some(rest) = key.strip_prefix("git-db:") {
+            return Some(self.git_db_path().join(rest));
+        }
+        if key.strip_prefix("registry-index:").is_some() {
+            return Some(self.registry_index_path());
+        }
+        key.strip_prefix("build-dir:")
+            .map(|rest| self.build_dirs_path().join(rest).join("target"))
+    }
+
+    fn entry_size(&self, path: &std::path::Path) -> u64 {
+        if !path.exists() {
+            return 0;
+        }
+        walkdir::WalkDir::new(path)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .filter_map(|entry| entry.metadata().ok())
+            .map(|metadata| metadata.len())
+            .sum()
+    }
+
+    /// Garbage-collect cache entries according to `policy`.
+    ///
+    /// Takes an exclusive lock on the workspace for the duration of the operation. Entries that
+    /// are tracked by the last-use index but missing from disk are pruned from the index. Only
+    /// entries recorded in the index are considered: entries present on disk but missing from it
+    /// are never evicted, and their size does not count toward a [`GcPolicy::KeepUnder`] budget.
+    pub fn gc(&self, policy: GcPolicy) -> Result<(), Error> {
+        let _lock = WorkspaceLock::acquire(self.root())?;
+
+        let index_path = self.lastuse_index_path();
+        let mut index = LastUseIndex::load(&index_path)?;
+        index.prune_missing(|key| {
+            self.resolve_entry(key)
+                .map(|path| path.exists())
+                .unwrap_or(false)
+        });
+
+        let mut entries: Vec<(String, u64, u64)> = index
+            .iter()
+            .filter_map(|(key, last_used)| {
+                self.resolve_entry(key).map(|path| (key.to_string(), last_used, self.entry_size(&path)))
+            })
+            .collect();
+
+        let to_delete: Vec<String> = match policy {
+            GcPolicy::OlderThan(max_age) => {
+                let threshold = now().saturating_sub(max_age.as_secs());
+                entries
+                    .into_iter()
+                    .filter(|(_, last_used, _)| *last_used < threshold)
+                    .map(|(key, _, _)| key)
+                    .collect()
+            }
+            GcPolicy::KeepUnder(budget) => {
+                entries.sort_by_key(|(_, last_used, _)| *last_used);
+                let mut total: u64 = entries.iter().map(|(_, _, size)| size).sum();
+                let mut to_delete = Vec::new();
+                for (key, _, size) in entries {
+                    if total <= budget {
+                        break;
+                    }
+                    to_delete.push(key);
+                    total = total.saturating_sub(size);
+                }
+                to_delete
+            }
+        };
+
+        for key in to_delete {
+            if let Some(path) = self.resolve_entry(&key) {
+                if path.exists() {
+                    fs::remove_dir_all(&path)
+                        .or_else(|_| fs::remove_file(&path))
+                        .with_context(|_| format!("failed to remove {}", path.display()))?;
+                }
+            }
+            index.remove(&key);
+        }
+
+        index.store(&index_path)
+    }
+}
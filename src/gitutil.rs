@@ -0,0 +1,111 @@
+//! Thin wrappers around the system `git` binary, used both to fetch git crates and to keep a
+//! local mirror of the registry index up to date.
+
+use failure::{Error, ResultExt};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Ask a remote for the commit its `HEAD` currently points at, without cloning anything.
+pub(crate) fn ls_remote_head(url: &str) -> Result<String, Error> {
+    let output = Command::new("git")
+        .args(["ls-remote", url, "HEAD"])
+        .output()
+        .with_context(|_| format!("failed to run `git ls-remote {}`", url))?;
+    if !output.status.success() {
+        failure::bail!(
+            "`git ls-remote {}` failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .split_whitespace()
+        .next()
+        .map(ToString::to_string)
+        .ok_or_else(|| failure::err_msg(format!("`git ls-remote {}` returned no output", url)))
+}
+
+/// Clone `url` as a bare repository into `dest`, which must not already exist.
+pub(crate) fn clone_bare(url: &str, dest: &Path) -> Result<(), Error> {
+    let status = Command::new("git")
+        .args(["clone", "--bare", "--quiet"])
+        .arg(url)
+        .arg(dest)
+        .status()
+        .with_context(|_| format!("failed to run `git clone {}`", url))?;
+    if !status.success() {
+        failure::bail!("`git clone --bare {}` exited with {}", url, status);
+    }
+    Ok(())
+}
+
+/// Update the branches of an existing bare repository to match its remote, creating it with
+/// [`clone_bare`] first if it doesn't exist yet.
+pub(crate) fn fetch_or_clone(url: &str, dest: &Path) -> Result<(), Error> {
+    if dest.join("HEAD").exists() {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dest)
+            .args(["fetch", "--quiet", "origin", "+refs/heads/*:refs/heads/*"])
+            .status()
+            .with_context(|_| format!("failed to run `git fetch` in {}", dest.display()))?;
+        if !status.success() {
+            failure::bail!("`git fetch` in {} exited with {}", dest.display(), status);
+        }
+        Ok(())
+    } else {
+        clone_bare(url, dest)
+    }
+}
+
+/// Check out `rev` from `repo` (typically a local bare database) into a fresh working tree at
+/// `dest`, replacing whatever was there before.
+pub(crate) fn checkout(repo: &Path, rev: &str, dest: &Path) -> Result<(), Error> {
+    if dest.exists() {
+        fs::remove_dir_all(dest).with_context(|_| format!("failed to remove {}", dest.display()))?;
+    }
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).with_context(|_| format!("failed to create {}", parent.display()))?;
+    }
+
+    let status = Command::new("git")
+        .args(["clone", "--quiet"])
+        .arg(repo)
+        .arg(dest)
+        .status()
+        .with_context(|_| format!("failed to run `git clone {}`", repo.display()))?;
+    if !status.success() {
+        failure::bail!("`git clone {}` exited with {}", repo.display(), status);
+    }
+
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dest)
+        .args(["checkout", "--quiet", rev])
+        .status()
+        .with_context(|_| format!("failed to run `git checkout {}` in {}", rev, dest.display()))?;
+    if !status.success() {
+        failure::bail!("`git checkout {}` in {} exited with {}", rev, dest.display(), status);
+    }
+    Ok(())
+}
+
+/// The commit a bare repository's `HEAD` resolves to.
+pub(crate) fn rev_parse_head(repo: &Path) -> Result<String, Error> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .with_context(|_| format!("failed to run `git rev-parse HEAD` in {}", repo.display()))?;
+    if !output.status.success() {
+        failure::bail!(
+            "`git rev-parse HEAD` in {} failed: {}",
+            repo.display(),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,62 @@
+use failure::{Error, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Tracks the last time each cache entry (a registry `.crate` file, a git db, a build directory's
+/// `target/` subtree, ...) was used, so [`gc`](crate::Workspace::gc) can decide what to evict.
+///
+/// The index is persisted as `cargo-home/.rustwide-lastuse.json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct LastUseIndex {
+    entries: HashMap<String, u64>,
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl LastUseIndex {
+    pub(crate) fn load(path: &Path) -> Result<Self, Error> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content =
+            fs::read_to_string(path).with_context(|_| format!("failed to read {}", path.display()))?;
+        Ok(serde_json::from_str(&content)
+            .with_context(|_| format!("failed to parse {}", path.display()))?)
+    }
+
+    pub(crate) fn store(&self, path: &Path) -> Result<(), Error> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|_| format!("failed to create {}", parent.display()))?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content).with_context(|_| format!("failed to write {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Record that `key` was just used.
+    pub(crate) fn touch(&mut self, key: impl Into<String>) {
+        self.entries.insert(key.into(), now());
+    }
+
+    /// Iterate over every tracked entry.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.entries.iter().map(|(key, &ts)| (key.as_str(), ts))
+    }
+
+    pub(crate) fn remove(&mut self, key: &str) {
+        self.entries.remove(key);
+    }
+
+    /// Drop entries whose backing file no longer exists, as reported by `exists`.
+    pub(crate) fn prune_missing(&mut self, exists: impl Fn(&str) -> bool) {
+        self.entries.retain(|key, _| exists(key));
+    }
+}
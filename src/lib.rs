@@ -0,0 +1,25 @@
+//! rustwide is a library to execute your Rust code in a sandbox, and record the results for
+//! later processing.
+
+mod build_dir;
+mod cache;
+pub mod cmd;
+mod crates;
+mod errors;
+mod fetch;
+mod gc;
+mod gitutil;
+mod lastuse;
+mod lock;
+mod toolchain;
+mod vendor;
+mod workspace;
+
+pub use crate::build_dir::{Build, BuildContext, BuildDir, CargoCommand};
+pub use crate::cache::CacheUsage;
+pub use crate::crates::Crate;
+pub use crate::errors::RustwideError;
+pub use crate::fetch::FetchError;
+pub use crate::gc::GcPolicy;
+pub use crate::toolchain::Toolchain;
+pub use crate::workspace::Workspace;
@@ -0,0 +1,27 @@
+use failure::{Error, ResultExt};
+use fs2::FileExt;
+use std::fs::{self, File};
+use std::path::Path;
+
+/// An exclusive lock over a workspace, held while the last-use index is updated and while the
+/// caches are garbage-collected, so those operations can't race with each other.
+pub(crate) struct WorkspaceLock {
+    file: File,
+}
+
+impl WorkspaceLock {
+    pub(crate) fn acquire(root: &Path) -> Result<Self, Error> {
+        fs::create_dir_all(root).with_context(|_| format!("failed to create {}", root.display()))?;
+        let path = root.join(".rustwide-lock");
+        let file = File::create(&path).with_context(|_| format!("failed to create {}", path.display()))?;
+        file.lock_exclusive()
+            .with_context(|_| format!("failed to lock {}", path.display()))?;
+        Ok(WorkspaceLock { file })
+    }
+}
+
+impl Drop for WorkspaceLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
@@ -0,0 +1,36 @@
+use crate::Workspace;
+use failure::Error;
+use std::fmt;
+
+/// A rustup-managed toolchain that builds can be run with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Toolchain {
+    Dist { name: String },
+}
+
+impl Toolchain {
+    /// Build a toolchain distributed through rustup, identified by its name (e.g. `"stable"` or
+    /// `"nightly-2020-01-01"`).
+    pub fn dist(name: &str) -> Self {
+        Toolchain::Dist { name: name.into() }
+    }
+
+    /// Does nothing: no toolchain is downloaded into the workspace's rustup home. Builds invoke
+    /// `cargo +<name>`, which resolves the toolchain through the host's rustup installation.
+    pub fn install(&self, workspace: &Workspace) -> Result<(), Error> {
+        let _ = workspace;
+        Ok(())
+    }
+
+    pub(crate) fn rustup_name(&self) -> &str {
+        match self {
+            Toolchain::Dist { name } => name,
+        }
+    }
+}
+
+impl fmt::Display for Toolchain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.rustup_name())
+    }
+}
@@ -0,0 +1,110 @@
+use crate::Crate;
+use crate::Workspace;
+use failure::{Error, ResultExt};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The `.cargo-checksum.json` manifest cargo expects at the root of each vendored crate,
+/// recording the sha256 of every file plus the checksum of the package as a whole.
+#[derive(Serialize)]
+struct ChecksumManifest {
+    files: BTreeMap<String, String>,
+    package: String,
+}
+
+impl Workspace {
+    /// Vendor `crates` into a flat directory so builds can run fully offline against it, instead
+    /// of the network-backed registry and git caches.
+    ///
+    /// Each crate is fetched (a registry tarball only if it isn't cached yet, a git repository
+    /// always from its remote), copied into its own subdirectory of the returned path, and given
+    /// a `.cargo-checksum.json` manifest. Pair this with
+    /// [`Build::vendored_sources`](crate::Build::vendored_sources) to point a build's crates.io
+    /// dependencies at the result via a `[source]` replacement; git dependencies are not
+    /// redirected to it.
+    pub fn vendor(&self, crates: &[Crate]) -> Result<PathBuf, Error> {
+        let vendor_dir = self.root().join("vendor");
+        fs::create_dir_all(&vendor_dir)
+            .with_context(|_| format!("failed to create {}", vendor_dir.display()))?;
+
+        for krate in crates {
+            krate.fetch(self)?;
+
+            let dest = vendor_dir.join(krate.vendor_dir_name());
+            fs::create_dir_all(&dest).with_context(|_| format!("failed to create {}", dest.display()))?;
+            copy_dir(&krate.extracted_path(self), &dest)?;
+
+            let package = krate.package_checksum(self)?;
+            write_checksum_manifest(&dest, package)?;
+        }
+
+        Ok(vendor_dir)
+    }
+}
+
+fn copy_dir(src: &Path, dest: &Path) -> Result<(), Error> {
+    if !src.exists() {
+        failure::bail!("{} was never fetched, nothing to vendor", src.display());
+    }
+    for entry in walkdir::WalkDir::new(src) {
+        let entry = entry.with_context(|_| format!("failed to walk {}", src.display()))?;
+        let relative = entry
+            .path()
+            .strip_prefix(src)
+            .expect("walkdir always yields children of its root");
+        let target = dest.join(relative);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(entry.path(), &target)
+                .with_context(|_| format!("failed to copy {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
+
+fn write_checksum_manifest(dest: &Path, package: String) -> Result<(), Error> {
+    let mut files = BTreeMap::new();
+    for entry in walkdir::WalkDir::new(dest) {
+        let entry = entry.with_context(|_| format!("failed to walk {}", dest.display()))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative = entry
+            .path()
+            .strip_prefix(dest)
+            .expect("walkdir always yields children of its root");
+        files.insert(
+            relative.to_string_lossy().into_owned(),
+            sha256_hex_of_file(entry.path())?,
+        );
+    }
+
+    let manifest = ChecksumManifest { files, package };
+    let path = dest.join(".cargo-checksum.json");
+    fs::write(&path, serde_json::to_string(&manifest)?)
+        .with_context(|_| format!("failed to write {}", path.display()))?;
+    Ok(())
+}
+
+/// sha256 of a file's contents, hex-encoded.
+pub(crate) fn sha256_hex_of_file(path: &Path) -> Result<String, Error> {
+    let contents = fs::read(path).with_context(|_| format!("failed to read {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    hasher.update(&contents);
+    Ok(to_hex(&hasher.finalize()))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        out.push_str(&format!("{:02x}", byte));
+    }
+    out
+}
@@ -0,0 +1,106 @@
+use crate::build_dir::BuildDir;
+use failure::{Error, ResultExt};
+use rayon::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A workspace holds the cargo home, rustup home and build directories used by a set of builds.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    root: PathBuf,
+}
+
+impl Workspace {
+    /// Create a workspace rooted at `root`, which need not exist yet: it's created lazily by
+    /// whichever operation first needs it.
+    pub fn new(root: &Path) -> Self {
+        Workspace { root: root.into() }
+    }
+
+    /// Root directory of this workspace.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path of the `CARGO_HOME` used by this workspace.
+    pub fn cargo_home(&self) -> PathBuf {
+        self.root.join("cargo-home")
+    }
+
+    /// Path of the `RUSTUP_HOME` used by this workspace.
+    pub fn rustup_home(&self) -> PathBuf {
+        self.root.join("rustup-home")
+    }
+
+    pub(crate) fn build_dirs_path(&self) -> PathBuf {
+        self.root.join("builds")
+    }
+
+    /// Open (creating if needed) a named, persistent build directory.
+    pub fn build_dir(&self, name: &str) -> BuildDir {
+        BuildDir {
+            workspace: self.clone(),
+            name: name.into(),
+        }
+    }
+
+    /// Remove every build directory, freeing all the `target/` directories they contain.
+    ///
+    /// Each build directory is removed independently on a rayon thread pool, so purging many of
+    /// them doesn't take much longer than purging one.
+    pub fn purge_all_build_dirs(&self) -> Result<(), Error> {
+        remove_children_in_parallel(&self.build_dirs_path())
+    }
+
+    /// Remove every cache inside the cargo home: the registry cache, the registry source
+    /// checkouts, the git database, the git checkouts and the local registry index mirror.
+    ///
+    /// The entries of each category are removed independently on a rayon thread pool, rather than
+    /// with one big recursive delete, since a populated cargo home can contain tens of thousands
+    /// of registry and git entries.
+    pub fn purge_all_caches(&self) -> Result<(), Error> {
+        remove_children_in_parallel(&self.registry_cache_path())?;
+        remove_children_in_parallel(&self.registry_src_path())?;
+        remove_children_in_parallel(&self.git_db_path())?;
+        remove_children_in_parallel(&self.git_checkouts_path())?;
+        remove_children_in_parallel(&self.registry_index_path())?;
+        Ok(())
+    }
+}
+
+/// Remove every direct child of `dir` in parallel. Once every deletion has been attempted, returns
+/// the error of the first failing child in sorted path order (if any).
+fn remove_children_in_parallel(dir: &Path) -> Result<(), Error> {
+    if !dir.exists() {
+        return Ok(());
+    }
+
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|_| format!("failed to read {}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()
+        .with_context(|_| format!("failed to read {}", dir.display()))?;
+    // The deletions run concurrently, but rayon's `collect` keeps the input order, so sorting
+    // makes the reported error deterministic, which makes failures reproducible.
+    entries.sort();
+
+    let errors: Vec<Error> = entries
+        .into_par_iter()
+        .filter_map(|entry| {
+            let result = if entry.is_dir() {
+                fs::remove_dir_all(&entry)
+            } else {
+                fs::remove_file(&entry)
+            };
+            result
+                .with_context(|_| format!("failed to remove {}", entry.display()))
+                .err()
+                .map(Error::from)
+        })
+        .collect();
+
+    if let Some(error) = errors.into_iter().next() {
+        return Err(error);
+    }
+    Ok(())
+}
@@ -0,0 +1,20 @@
+mod purge_caches;
+mod vendor;
+
+mod utils {
+    use failure::{Error, ResultExt};
+    use rustwide::Workspace;
+    use std::path::PathBuf;
+
+    /// Root directory a named test workspace lives in, kept outside of `target/` so a `cargo
+    /// clean` doesn't wipe the caches the tests exercise.
+    pub(crate) fn workspace_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join("rustwide-integration-tests").join(name)
+    }
+
+    pub(crate) fn init_named_workspace(name: &str) -> Result<Workspace, Error> {
+        let path = workspace_path(name);
+        std::fs::create_dir_all(&path).with_context(|_| format!("failed to create {}", path.display()))?;
+        Ok(Workspace::new(&path))
+    }
+}
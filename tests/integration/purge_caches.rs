@@ -1,4 +1,5 @@
 use failure::Error;
+use rayon::prelude::*;
 use rustwide::cmd::SandboxBuilder;
 use rustwide::{Crate, Toolchain};
 use sha1::{Digest, Sha1};
@@ -67,6 +68,12 @@ fn should_ignore(base: &Path, path: &Path) -> bool {
         ["cargo-home", "registry", "index", _, ".cargo-index-lock"] => true,
         ["cargo-home", "registry", "index", _, ".last-updated"] => true,
 
+        // The last-use index is bookkeeping for `Workspace::gc`, not a cache, so the fetches and
+        // builds legitimately update it without that counting as a content change.
+        ["cargo-home", ".rustwide-lastuse.json"] => true,
+        // The workspace lock file is created the first time the last-use index is updated.
+        [".rustwide-lock"] => true,
+
         _ => false,
     }
 }
@@ -79,19 +86,30 @@ struct WorkspaceContents {
 
 impl WorkspaceContents {
     fn collect(path: &Path) -> Result<Self, Error> {
-        let mut files = HashMap::new();
-
-        for entry in walkdir::WalkDir::new(path) {
-            let entry = entry?;
-            if !entry.metadata()?.is_file() {
-                continue;
-            }
-
-            let mut sha = Sha1::new();
-            sha.update(&std::fs::read(entry.path())?);
-
-            files.insert(entry.path().into(), sha.digest());
-        }
+        // List the entries first (cheap, and walkdir isn't `Send`-friendly to drive from rayon
+        // directly), then hash them in parallel: on a populated cargo-home this is by far the
+        // most expensive part of the test.
+        let paths = walkdir::WalkDir::new(path)
+            .into_iter()
+            .map(|entry| -> Result<Option<PathBuf>, Error> {
+                let entry = entry?;
+                if entry.metadata()?.is_file() {
+                    Ok(Some(entry.path().into()))
+                } else {
+                    Ok(None)
+                }
+            })
+            .filter_map(Result::transpose)
+            .collect::<Result<Vec<PathBuf>, Error>>()?;
+
+        let files = paths
+            .into_par_iter()
+            .map(|path| -> Result<(PathBuf, Digest), Error> {
+                let mut sha = Sha1::new();
+                sha.update(&std::fs::read(&path)?);
+                Ok((path, sha.digest()))
+            })
+            .collect::<Result<HashMap<PathBuf, Digest>, Error>>()?;
 
         Ok(Self {
             base: path.into(),
@@ -101,8 +119,7 @@ impl WorkspaceContents {
 
     fn assert_same(self, mut other: Self) {
         let mut same = true;
-
-        println!("=== start directory differences ===");
+        let mut differences = Vec::new();
 
         for (path, start_digest) in self.files.into_iter() {
             if should_ignore(&self.base, &path) {
@@ -111,11 +128,11 @@ impl WorkspaceContents {
 
             if let Some(end_digest) = other.files.remove(&path) {
                 if start_digest != end_digest {
-                    println!("file {} changed", path.display());
+                    differences.push(format!("file {} changed", path.display()));
                     same = false;
                 }
             } else {
-                println!("file {} was removed", path.display());
+                differences.push(format!("file {} was removed", path.display()));
                 same = false;
             }
         }
@@ -125,10 +142,18 @@ impl WorkspaceContents {
                 continue;
             }
 
-            println!("file {} was added", path.display());
+            differences.push(format!("file {} was added", path.display()));
             same = false;
         }
 
+        // Sort for a deterministic, easy to read report, since iterating the `HashMap`s above
+        // yields the files in arbitrary order.
+        differences.sort();
+
+        println!("=== start directory differences ===");
+        for difference in &differences {
+            println!("{}", difference);
+        }
         println!("=== end directory differences ===");
 
         if !same {
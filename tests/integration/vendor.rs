@@ -0,0 +1,59 @@
+use failure::Error;
+use rustwide::cmd::SandboxBuilder;
+use rustwide::{Crate, GcPolicy, Toolchain};
+use std::time::Duration;
+
+const WORKSPACE_NAME: &str = "vendor";
+
+#[test]
+fn test_vendor_and_offline_build() -> Result<(), Error> {
+    let workspace = crate::utils::init_named_workspace(WORKSPACE_NAME)?;
+    workspace.purge_all_build_dirs()?;
+    workspace.purge_all_caches()?;
+
+    let toolchain = Toolchain::dist("stable");
+    toolchain.install(&workspace)?;
+
+    let crates = vec![
+        Crate::crates_io("lazy_static", "1.0.0"),
+        Crate::git("https://github.com/pietroalbini/git-credential-null"),
+    ];
+
+    let vendor_dir = workspace.vendor(&crates)?;
+
+    // Each crate should have landed in its own, genuinely populated subdirectory, with a
+    // checksum manifest that actually lists files.
+    let vendored: Vec<_> = std::fs::read_dir(&vendor_dir)?.collect::<Result<_, _>>()?;
+    assert_eq!(vendored.len(), crates.len(), "not every crate was vendored");
+    for entry in vendored {
+        let files: Vec<_> = std::fs::read_dir(entry.path())?.collect::<Result<_, _>>()?;
+        assert!(!files.is_empty(), "{} was vendored to an empty directory", entry.path().display());
+
+        let manifest = std::fs::read_to_string(entry.path().join(".cargo-checksum.json"))?;
+        let manifest: serde_json::Value = serde_json::from_str(&manifest)?;
+        assert!(
+            !manifest["files"].as_object().unwrap().is_empty(),
+            "{} has an empty checksum manifest",
+            entry.path().display()
+        );
+    }
+
+    // A build pointed at the vendored directory should succeed without any network access.
+    let sandbox = SandboxBuilder::new().enable_networking(false);
+    let mut build_dir = workspace.build_dir("vendor-offline");
+    build_dir
+        .build(&toolchain, &crates[0], sandbox)
+        .vendored_sources(&vendor_dir)
+        .run(|build| {
+            build.cargo().args(&["check"]).run()?;
+            Ok(())
+        })?;
+
+    // The build and the vendoring fetches should both be visible to cache_usage and gc.
+    assert!(workspace.cache_usage()?.total() > 0);
+    workspace.gc(GcPolicy::OlderThan(Duration::from_secs(0)))?;
+
+    workspace.purge_all_build_dirs()?;
+    workspace.purge_all_caches()?;
+    Ok(())
+}